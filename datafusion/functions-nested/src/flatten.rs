@@ -18,19 +18,20 @@
 //! [`ScalarUDFImpl`] definitions for flatten function.
 
 use crate::utils::make_scalar_function;
-use arrow::array::{ArrayRef, GenericListArray, OffsetSizeTrait};
+use arrow::array::{ArrayRef, FixedSizeListArray, GenericListArray, OffsetSizeTrait};
 use arrow::buffer::OffsetBuffer;
 use arrow::datatypes::{
     DataType,
     DataType::{FixedSizeList, LargeList, List, Null},
 };
 use datafusion_common::cast::{
-    as_generic_list_array, as_large_list_array, as_list_array,
+    as_fixed_size_list_array, as_generic_list_array, as_int64_array,
+    as_large_list_array, as_list_array,
 };
-use datafusion_common::{exec_err, utils::take_function_args, Result};
+use datafusion_common::{exec_err, internal_err, plan_err, Result, ScalarValue};
 use datafusion_expr::{
-    ArrayFunctionSignature, ColumnarValue, Documentation, ScalarUDFImpl, Signature,
-    TypeSignature, Volatility,
+    ArrayFunctionSignature, ColumnarValue, Documentation, ReturnInfo, ReturnTypeArgs,
+    ScalarUDFImpl, Signature, TypeSignature, TypeSignatureClass, Volatility,
 };
 use datafusion_macros::user_doc;
 use std::any::Any;
@@ -40,13 +41,37 @@ make_udf_expr_and_func!(
     Flatten,
     flatten,
     array,
-    "flattens an array of arrays into a single array.",
+    "flattens the outermost level of an array of arrays into a single array.",
     flatten_udf
 );
 
+make_udf_expr_and_func!(
+    FlattenDeep,
+    array_flatten_deep,
+    array,
+    "recursively flattens an array of arrays into a single array.",
+    array_flatten_deep_udf
+);
+
+/// `flatten` and `array_flatten_deep` only differ in how many levels of nesting they
+/// remove by default, so they share this signature: one argument (array-only), or two
+/// (array plus a depth that can arrive as any integer type and is coerced to Int64).
+fn flatten_signature() -> Signature {
+    Signature::one_of(
+        vec![
+            TypeSignature::ArraySignature(ArrayFunctionSignature::RecursiveArray),
+            TypeSignature::Coercible(vec![
+                TypeSignatureClass::Array,
+                TypeSignatureClass::Integer,
+            ]),
+        ],
+        Volatility::Immutable,
+    )
+}
+
 #[user_doc(
     doc_section(label = "Array Functions"),
-    description = "Converts an array of arrays to a flat array.\n\n- Applies to any depth of nested arrays\n- Does not change arrays that are already flat\n\nThe flattened array contains all the elements from all source arrays.",
+    description = "Converts an array of arrays into an array by removing one level of nesting, matching the Postgres `flatten` semantics.\n\n- Does not change arrays that are already flat\n- An optional second argument removes more than one level: a negative depth removes all levels (see [`array_flatten_deep`](#array_flatten_deep)), and a depth of `0` returns the array unchanged.",
     syntax_example = "flatten(array)",
     sql_example = r#"```sql
 > select flatten([[1, 2], [3, 4]]);
@@ -55,10 +80,21 @@ make_udf_expr_and_func!(
 +------------------------------+
 | [1, 2, 3, 4]                 |
 +------------------------------+
+
+> select flatten([[[1, 2]], [[3, 4]]]);
++-----------------------------------+
+| flatten(List([[1,2]], [[3,4]]))   |
++-----------------------------------+
+| [[1, 2], [3, 4]]                  |
++-----------------------------------+
 ```"#,
     argument(
         name = "array",
         description = "Array expression. Can be a constant, column, or function, and any combination of array operators."
+    ),
+    argument(
+        name = "n",
+        description = "Number of levels of nesting to remove. Defaults to `1`. A negative value removes all levels. Must be a literal, and (if given as an array-valued expression) the same value for every row. Optional."
     )
 )]
 #[derive(Debug)]
@@ -76,13 +112,7 @@ impl Default for Flatten {
 impl Flatten {
     pub fn new() -> Self {
         Self {
-            signature: Signature {
-                // TODO (https://github.com/apache/datafusion/issues/13757) flatten should be single-step, not recursive
-                type_signature: TypeSignature::ArraySignature(
-                    ArrayFunctionSignature::RecursiveArray,
-                ),
-                volatility: Volatility::Immutable,
-            },
+            signature: flatten_signature(),
             aliases: vec![],
         }
     }
@@ -101,34 +131,109 @@ impl ScalarUDFImpl for Flatten {
         &self.signature
     }
 
-    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
-        fn get_base_type(data_type: &DataType) -> Result<DataType> {
-            match data_type {
-                List(field) | FixedSizeList(field, _)
-                    if matches!(field.data_type(), List(_) | FixedSizeList(_, _)) =>
-                {
-                    get_base_type(field.data_type())
-                }
-                LargeList(field) if matches!(field.data_type(), LargeList(_)) => {
-                    get_base_type(field.data_type())
-                }
-                Null | List(_) | LargeList(_) => Ok(data_type.to_owned()),
-                FixedSizeList(field, _) => Ok(List(Arc::clone(field))),
-                _ => exec_err!(
-                    "Not reachable, data_type should be List, LargeList or FixedSizeList"
-                ),
-            }
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        internal_err!("return_type_from_args should be called instead")
+    }
+
+    fn return_type_from_args(&self, args: ReturnTypeArgs) -> Result<ReturnInfo> {
+        let remaining_depth = resolve_depth(&args, Some(1))?;
+        let data_type = get_base_type(&args.arg_types[0], remaining_depth)?;
+        Ok(ReturnInfo::new_nullable(data_type))
+    }
+
+    fn invoke_with_args(
+        &self,
+        args: datafusion_expr::ScalarFunctionArgs,
+    ) -> Result<ColumnarValue> {
+        make_scalar_function(|args| flatten_inner(args, Some(1)))(&args.args)
+    }
+
+    fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    fn documentation(&self) -> Option<&Documentation> {
+        self.doc()
+    }
+}
+
+#[user_doc(
+    doc_section(label = "Array Functions"),
+    description = "Converts an array of arrays to a flat array, recursing to the base element type.\n\n- Applies to any depth of nested arrays\n- Does not change arrays that are already flat\n\nAn optional second argument limits how many levels of nesting are removed instead of recursing fully; a depth of `0` returns the array unchanged.\n\nThe flattened array contains all the elements from all source arrays.",
+    syntax_example = "array_flatten_deep(array)",
+    sql_example = r#"```sql
+> select array_flatten_deep([[1, 2], [3, 4]]);
++----------------------------------------+
+| array_flatten_deep(List([1,2], [3,4])) |
++----------------------------------------+
+| [1, 2, 3, 4]                           |
++----------------------------------------+
+
+> select array_flatten_deep([[[1, 2]], [[3, 4]]]);
++--------------------------------------------+
+| array_flatten_deep(List([[1,2]], [[3,4]])) |
++--------------------------------------------+
+| [1, 2, 3, 4]                               |
++--------------------------------------------+
+```"#,
+    argument(
+        name = "array",
+        description = "Array expression. Can be a constant, column, or function, and any combination of array operators."
+    ),
+    argument(
+        name = "n",
+        description = "Number of levels of nesting to remove. A negative or omitted value removes all levels. Must be a literal, and (if given as an array-valued expression) the same value for every row. Optional."
+    )
+)]
+#[derive(Debug)]
+pub struct FlattenDeep {
+    signature: Signature,
+    aliases: Vec<String>,
+}
+
+impl Default for FlattenDeep {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FlattenDeep {
+    pub fn new() -> Self {
+        Self {
+            signature: flatten_signature(),
+            aliases: vec![],
         }
+    }
+}
+
+impl ScalarUDFImpl for FlattenDeep {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
 
-        let data_type = get_base_type(&arg_types[0])?;
-        Ok(data_type)
+    fn name(&self) -> &str {
+        "array_flatten_deep"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        internal_err!("return_type_from_args should be called instead")
+    }
+
+    fn return_type_from_args(&self, args: ReturnTypeArgs) -> Result<ReturnInfo> {
+        let remaining_depth = resolve_depth(&args, None)?;
+        let data_type = get_base_type(&args.arg_types[0], remaining_depth)?;
+        Ok(ReturnInfo::new_nullable(data_type))
     }
 
     fn invoke_with_args(
         &self,
         args: datafusion_expr::ScalarFunctionArgs,
     ) -> Result<ColumnarValue> {
-        make_scalar_function(flatten_inner)(&args.args)
+        make_scalar_function(|args| flatten_inner(args, None))(&args.args)
     }
 
     fn aliases(&self) -> &[String] {
@@ -140,19 +245,126 @@ impl ScalarUDFImpl for Flatten {
     }
 }
 
-/// Flatten SQL function
-pub fn flatten_inner(args: &[ArrayRef]) -> Result<ArrayRef> {
-    let [array] = take_function_args("flatten", args)?;
+/// Resolves the remaining-depth to use for type inference: an explicit, known literal
+/// second argument wins (a literal `NULL` falls back to `default_depth`, the caller's
+/// own convention for "no depth given"); a second argument that isn't resolvable as a
+/// literal at this point is rejected outright, rather than silently assumed to be
+/// `default_depth`, since `flatten_inner` resolves that same argument from the actual
+/// runtime values and the two must never disagree on the output type.
+fn resolve_depth(args: &ReturnTypeArgs, default_depth: Option<i64>) -> Result<Option<i64>> {
+    if args.arg_types.len() < 2 {
+        return Ok(default_depth);
+    }
+
+    let Some(depth) = args.scalar_arguments.get(1).and_then(|opt| *opt) else {
+        return plan_err!("flatten's depth argument must be a literal");
+    };
+    let depth = depth.cast_to(&DataType::Int64)?;
+
+    match depth {
+        ScalarValue::Int64(depth) => Ok(depth.or(default_depth)),
+        _ => internal_err!("flatten's depth argument should have been coerced to Int64"),
+    }
+}
+
+/// Peels wrapper `Field`s off a (possibly nested) list `data_type`, mirroring how many
+/// levels `flatten_internal` will actually remove at execution time.
+///
+/// `remaining_depth` follows the same convention as `flatten_internal`: `None` or a
+/// negative value means "remove every level" (today's fully-recursive behavior), `Some(0)`
+/// leaves the type untouched, and `Some(n)` peels at most `n` levels.
+fn get_base_type(data_type: &DataType, remaining_depth: Option<i64>) -> Result<DataType> {
+    // `flatten_internal` is generic over one `OffsetSizeTrait` fixed by the top-level
+    // array's own type (`List` or `FixedSizeList` use `i32`, `LargeList` uses `i64`),
+    // and it keeps using that same offset size for every `FixedSizeList` child it
+    // converts while descending, no matter how deep. So whether a terminal
+    // `FixedSizeList` ends up typed as `List` or `LargeList` depends on the outer
+    // array this whole call started from, not on the `FixedSizeList` itself.
+    let large = matches!(data_type, LargeList(_));
+    if remaining_depth == Some(0) {
+        return Ok(data_type.to_owned());
+    }
+    get_base_type_inner(data_type, remaining_depth, large)
+}
+
+fn get_base_type_inner(
+    data_type: &DataType,
+    remaining_depth: Option<i64>,
+    large: bool,
+) -> Result<DataType> {
+    if remaining_depth == Some(0) {
+        // A nested `FixedSizeList` reached here has already been converted by
+        // `flatten_internal`, so it comes out as a `List`/`LargeList`.
+        return Ok(match data_type {
+            FixedSizeList(field, _) if large => LargeList(Arc::clone(field)),
+            FixedSizeList(field, _) => List(Arc::clone(field)),
+            _ => data_type.to_owned(),
+        });
+    }
+    let next_depth = remaining_depth.map(|depth| depth - 1);
+
+    match data_type {
+        List(field) | FixedSizeList(field, _)
+            if matches!(field.data_type(), List(_) | FixedSizeList(_, _)) =>
+        {
+            get_base_type_inner(field.data_type(), next_depth, large)
+        }
+        LargeList(field)
+            if matches!(field.data_type(), LargeList(_) | FixedSizeList(_, _)) =>
+        {
+            get_base_type_inner(field.data_type(), next_depth, large)
+        }
+        Null | List(_) | LargeList(_) => Ok(data_type.to_owned()),
+        FixedSizeList(field, _) if large => Ok(LargeList(Arc::clone(field))),
+        FixedSizeList(field, _) => Ok(List(Arc::clone(field))),
+        _ => {
+            exec_err!("Not reachable, data_type should be List, LargeList or FixedSizeList")
+        }
+    }
+}
+
+/// Flatten SQL function, shared by `flatten` (`default_depth: Some(1)`) and
+/// `array_flatten_deep` (`default_depth: None`, i.e. fully recursive).
+pub fn flatten_inner(args: &[ArrayRef], default_depth: Option<i64>) -> Result<ArrayRef> {
+    if args.is_empty() || args.len() > 2 {
+        return exec_err!("flatten expects 1 or 2 arguments, got {}", args.len());
+    }
+    let array = &args[0];
+
+    // An explicit depth always wins; otherwise fall back to the caller's default. A
+    // negative depth preserves the fully-recursive behavior either way. The depth is
+    // applied to the whole batch at once, so every row must agree on the same value.
+    let remaining_depth = match args.get(1) {
+        Some(depth_array) if depth_array.is_empty() => default_depth,
+        Some(depth_array) => {
+            let depth_array = as_int64_array(depth_array)?;
+            let first = depth_array.is_valid(0).then(|| depth_array.value(0));
+            if depth_array.iter().any(|depth| depth != first) {
+                return exec_err!(
+                    "flatten's depth argument must be the same value for every row"
+                );
+            }
+            first.or(default_depth)
+        }
+        None => default_depth,
+    };
 
     match array.data_type() {
         List(_) => {
             let list_arr = as_list_array(&array)?;
-            let flattened_array = flatten_internal::<i32>(list_arr.clone(), None)?;
+            let flattened_array = flatten_internal::<i32>(list_arr.clone(), remaining_depth)?;
             Ok(Arc::new(flattened_array) as ArrayRef)
         }
         LargeList(_) => {
             let list_arr = as_large_list_array(&array)?;
-            let flattened_array = flatten_internal::<i64>(list_arr.clone(), None)?;
+            let flattened_array = flatten_internal::<i64>(list_arr.clone(), remaining_depth)?;
+            Ok(Arc::new(flattened_array) as ArrayRef)
+        }
+        FixedSizeList(_, _) if remaining_depth == Some(0) => Ok(Arc::clone(array)),
+        FixedSizeList(_, _) => {
+            let list_arr = as_fixed_size_list_array(&array)?;
+            let list_arr = fixed_size_list_to_list_array::<i32>(list_arr)?;
+            let flattened_array = flatten_internal::<i32>(list_arr, remaining_depth)?;
             Ok(Arc::new(flattened_array) as ArrayRef)
         }
         Null => Ok(Arc::clone(array)),
@@ -162,35 +374,107 @@ pub fn flatten_inner(args: &[ArrayRef]) -> Result<ArrayRef> {
     }
 }
 
+/// Flattens `list_arr` by composing the offsets of each nested level it recurses
+/// through into a single `OffsetBuffer`, in one top-down pass over the nesting
+/// followed by one fold over the collected levels.
+///
+/// This never clones a nested array: each level is inspected through borrowing
+/// accessors (`offsets`/`field`/`values`), and the innermost values buffer that ends
+/// up in the result is the same `Arc` the input array already held.
 fn flatten_internal<O: OffsetSizeTrait>(
     list_arr: GenericListArray<O>,
-    indexes: Option<OffsetBuffer<O>>,
+    remaining_depth: Option<i64>,
 ) -> Result<GenericListArray<O>> {
-    let (field, offsets, values, _) = list_arr.clone().into_parts();
-    let data_type = field.data_type();
+    if remaining_depth == Some(0) {
+        return Ok(list_arr);
+    }
 
-    match data_type {
-        // Recursively get the base offsets for flattened array
-        List(_) | LargeList(_) => {
-            let sub_list = as_generic_list_array::<O>(&values)?;
-            if let Some(indexes) = indexes {
-                let offsets = get_offsets_for_flatten(offsets, indexes);
-                flatten_internal::<O>(sub_list.clone(), Some(offsets))
-            } else {
-                flatten_internal::<O>(sub_list.clone(), Some(offsets))
-            }
+    let mut levels: Vec<OffsetBuffer<O>> = vec![list_arr.offsets().clone()];
+    let mut field = Arc::clone(list_arr.field());
+    let mut values = Arc::clone(list_arr.values());
+    let mut depth = remaining_depth;
+
+    // Walk down through the nesting once, top-down, collecting each level's
+    // offsets. `field`/`values` end up describing the innermost level reached.
+    // `depth` counts down the number of compositions still to perform, so it is
+    // checked before each descent rather than pre-decremented: `Some(n)` must
+    // result in exactly `n` levels being composed, not `n - 1`.
+    loop {
+        if depth == Some(0) {
+            break;
         }
-        // Reach the base level, create a new list array
-        _ => {
-            if let Some(indexes) = indexes {
-                let offsets = get_offsets_for_flatten(offsets, indexes);
-                let list_arr = GenericListArray::<O>::new(field, offsets, values, None);
-                Ok(list_arr)
-            } else {
-                Ok(list_arr)
+
+        let (next_field, next_offsets, next_values) = match field.data_type() {
+            List(_) | LargeList(_) => {
+                let sub_list = as_generic_list_array::<O>(&values)?;
+                (
+                    Arc::clone(sub_list.field()),
+                    sub_list.offsets().clone(),
+                    Arc::clone(sub_list.values()),
+                )
             }
-        }
+            // A fixed-size list child is converted to a `GenericListArray<O>` so it
+            // flows through the same offset-composing path as a variable-size child.
+            FixedSizeList(_, _) => {
+                let sub_list =
+                    fixed_size_list_to_list_array::<O>(as_fixed_size_list_array(&values)?)?;
+                (
+                    Arc::clone(sub_list.field()),
+                    sub_list.offsets().clone(),
+                    Arc::clone(sub_list.values()),
+                )
+            }
+            _ => break,
+        };
+
+        levels.push(next_offsets);
+        field = next_field;
+        values = next_values;
+        depth = depth.map(|depth| depth - 1);
+    }
+
+    // Nothing was composed: return the input unchanged, preserving its validity
+    // buffer exactly as before rather than rebuilding an equivalent array.
+    if levels.len() == 1 {
+        return Ok(list_arr);
     }
+
+    // Fold the collected offsets top-down into a single composed `OffsetBuffer`: each
+    // level's offsets are resolved through the positions already composed from the
+    // levels above it, so every entry is resolved by exactly one chained gather.
+    let mut levels = levels.into_iter();
+    let mut composed = levels.next().expect("at least the top level is present");
+    for level_offsets in levels {
+        composed = get_offsets_for_flatten(level_offsets, composed);
+    }
+
+    // The composed offsets describe the same rows as `list_arr` itself (folding
+    // never changes the row count), so the original top-level validity buffer
+    // still applies: a null row must stay null rather than turn into an empty list.
+    let nulls = list_arr.nulls().cloned();
+    Ok(GenericListArray::<O>::new(field, composed, values, nulls))
+}
+
+/// Converts a `FixedSizeListArray` into an equivalent `GenericListArray<O>` by building
+/// offsets that are simply multiples of the fixed list length, reusing the existing
+/// values and validity buffers without copying any array data.
+fn fixed_size_list_to_list_array<O: OffsetSizeTrait>(
+    list_arr: &FixedSizeListArray,
+) -> Result<GenericListArray<O>> {
+    let (field, value_length) = match list_arr.data_type() {
+        FixedSizeList(field, value_length) => (Arc::clone(field), *value_length as usize),
+        _ => return exec_err!("Expected FixedSizeList data type"),
+    };
+
+    let offsets = OffsetBuffer::new(
+        (0..=list_arr.len())
+            .map(|i| O::usize_as(i * value_length))
+            .collect(),
+    );
+    let values = Arc::clone(list_arr.values());
+    let nulls = list_arr.nulls().cloned();
+
+    Ok(GenericListArray::<O>::new(field, offsets, values, nulls))
 }
 
 // Create new offsets that are equivalent to `flatten` the array.
@@ -199,9 +483,296 @@ fn get_offsets_for_flatten<O: OffsetSizeTrait>(
     indexes: OffsetBuffer<O>,
 ) -> OffsetBuffer<O> {
     let buffer = offsets.into_inner();
+    // Guard against a degenerate (empty) parent buffer: there is no value to gather,
+    // so every composed entry collapses to zero.
+    if buffer.is_empty() {
+        return OffsetBuffer::new(vec![O::usize_as(0); indexes.len()].into());
+    }
     let offsets: Vec<O> = indexes
         .iter()
-        .map(|i| buffer[i.to_usize().unwrap()])
+        .map(|i| {
+            let idx = i.to_usize().unwrap_or(0).min(buffer.len() - 1);
+            buffer[idx]
+        })
         .collect();
     OffsetBuffer::new(offsets.into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Int32Array};
+    use arrow::buffer::NullBuffer;
+    use arrow::datatypes::Field;
+
+    /// Builds a flat `List<Int32>` array from `offsets`/`values`, e.g.
+    /// `int32_list(vec![0, 2, 4], vec![1, 2, 3, 4])` is `[[1, 2], [3, 4]]`.
+    fn int32_list(offsets: Vec<i32>, values: Vec<i32>) -> GenericListArray<i32> {
+        let field = Arc::new(Field::new("item", DataType::Int32, true));
+        GenericListArray::<i32>::new(
+            field,
+            OffsetBuffer::new(offsets.into()),
+            Arc::new(Int32Array::from(values)),
+            None,
+        )
+    }
+
+    /// Wraps `inner` in one more level of list nesting, grouping its rows
+    /// according to `offsets`.
+    fn wrap_list(inner: GenericListArray<i32>, offsets: Vec<i32>) -> GenericListArray<i32> {
+        let field = Arc::new(Field::new("item", inner.data_type().to_owned(), true));
+        GenericListArray::<i32>::new(
+            field,
+            OffsetBuffer::new(offsets.into()),
+            Arc::new(inner),
+            None,
+        )
+    }
+
+    fn int32_values(array: &dyn Array) -> &[i32] {
+        array.as_any().downcast_ref::<Int32Array>().unwrap().values()
+    }
+
+    /// A single row holding `[[1, 2], [3, 4]]`, matching this file's own doc example.
+    fn two_level() -> GenericListArray<i32> {
+        wrap_list(int32_list(vec![0, 2, 4], vec![1, 2, 3, 4]), vec![0, 2])
+    }
+
+    /// A single row holding `[[[1, 2], [3, 4]]]`: one more level of nesting than
+    /// [`two_level`].
+    fn three_level() -> GenericListArray<i32> {
+        wrap_list(two_level(), vec![0, 1])
+    }
+
+    #[test]
+    fn flatten_default_depth_removes_one_level() {
+        // This is a regression test for a bug where the default, single-step
+        // `flatten` call was a complete no-op on arrays nested 2+ levels deep:
+        // flatten([[1, 2], [3, 4]]) must be [1, 2, 3, 4], not [[1, 2], [3, 4]].
+        let result = flatten_internal(two_level(), Some(1)).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(int32_values(result.values()), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn flatten_preserves_top_level_nulls() {
+        // `[[1, 2], null, [3, 4]]` flattened by one level must keep its null row
+        // null, not silently turn it into an empty list.
+        let inner = int32_list(vec![0, 1, 2, 3, 4], vec![1, 2, 3, 4]);
+        let field = Arc::new(Field::new("item", inner.data_type().to_owned(), true));
+        let nulls = NullBuffer::from(vec![true, false, true]);
+        let input = GenericListArray::<i32>::new(
+            field,
+            OffsetBuffer::new(vec![0, 2, 2, 4].into()),
+            Arc::new(inner),
+            Some(nulls),
+        );
+
+        let result = flatten_internal(input, Some(1)).unwrap();
+        assert_eq!(result.len(), 3);
+        assert!(result.is_valid(0));
+        assert!(result.is_null(1));
+        assert!(result.is_valid(2));
+        assert_eq!(int32_values(result.value(0).as_ref()), &[1, 2]);
+        assert_eq!(int32_values(result.value(2).as_ref()), &[3, 4]);
+    }
+
+    #[test]
+    fn flatten_depth_zero_is_a_no_op() {
+        let input = two_level();
+        let result = flatten_internal(input, Some(0)).unwrap();
+        assert_eq!(result.len(), 1);
+        let child = as_generic_list_array::<i32>(result.values()).unwrap();
+        assert_eq!(child.len(), 2);
+    }
+
+    #[test]
+    fn flatten_depth_one_on_three_levels_peels_only_outer() {
+        // flatten([[[1, 2], [3, 4]]], 1) == [[1, 2], [3, 4]]
+        let result = flatten_internal(three_level(), Some(1)).unwrap();
+        assert_eq!(result.len(), 1);
+        let child = as_generic_list_array::<i32>(result.values()).unwrap();
+        assert_eq!(child.len(), 2);
+        assert_eq!(int32_values(child.value(0).as_ref()), &[1, 2]);
+        assert_eq!(int32_values(child.value(1).as_ref()), &[3, 4]);
+    }
+
+    #[test]
+    fn flatten_depth_two_recurses_two_levels() {
+        // flatten([[[1, 2], [3, 4]]], 2) == [1, 2, 3, 4]
+        let result = flatten_internal(three_level(), Some(2)).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(int32_values(result.values()), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn flatten_negative_depth_matches_fully_recursive() {
+        let via_negative = flatten_internal(three_level(), Some(-1)).unwrap();
+        let via_none = flatten_internal(three_level(), None).unwrap();
+        assert_eq!(int32_values(via_negative.values()), &[1, 2, 3, 4]);
+        assert_eq!(int32_values(via_none.values()), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn flatten_inner_reads_explicit_depth_argument() {
+        let depth_arg: ArrayRef = Arc::new(arrow::array::Int64Array::from(vec![1]));
+        let result = flatten_inner(&[Arc::new(three_level()), depth_arg], Some(1)).unwrap();
+        let list = as_list_array(&result).unwrap();
+        assert_eq!(list.len(), 1);
+        let child = as_generic_list_array::<i32>(list.values()).unwrap();
+        assert_eq!(child.len(), 2);
+    }
+
+    #[test]
+    fn flatten_inner_rejects_varying_depth_per_row() {
+        // The depth argument is applied to the whole batch at once, so a column of
+        // depths that disagree across rows must error rather than silently using
+        // only the first row's value.
+        let array = GenericListArray::<i32>::new(
+            Arc::new(Field::new("item", DataType::Int32, true)),
+            OffsetBuffer::new(vec![0, 1, 2].into()),
+            Arc::new(Int32Array::from(vec![1, 2])),
+            None,
+        );
+        let depth_arg: ArrayRef = Arc::new(arrow::array::Int64Array::from(vec![1, 2]));
+        let err = flatten_inner(&[Arc::new(array), depth_arg], Some(1)).unwrap_err();
+        assert!(err.to_string().contains("same value for every row"));
+    }
+
+    #[test]
+    fn resolve_depth_falls_back_to_default_for_a_literal_null() {
+        // A literal `NULL` second argument is a known value (there just isn't a depth
+        // override), so it still resolves to `default_depth`.
+        let arg_types = [
+            DataType::List(Arc::new(Field::new("item", DataType::Int32, true))),
+            DataType::Int64,
+        ];
+        let null_depth = ScalarValue::Int64(None);
+
+        let literal_null = ReturnTypeArgs {
+            arg_types: &arg_types,
+            scalar_arguments: &[None, Some(&null_depth)],
+            nullables: &[false, true],
+        };
+        assert_eq!(resolve_depth(&literal_null, Some(1)).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn resolve_depth_rejects_a_non_foldable_second_argument() {
+        // Unlike a literal NULL, a second argument that `scalar_arguments` has no
+        // value for at all (a column, or any not-yet-constant-folded expression such
+        // as `1 + 1`) must be rejected outright rather than silently assumed to be
+        // `default_depth`: `flatten_inner` resolves that same argument from the
+        // actual runtime values, which may not match `default_depth`, so plan time
+        // and run time would otherwise disagree on the output type.
+        let arg_types = [
+            DataType::List(Arc::new(Field::new("item", DataType::Int32, true))),
+            DataType::Int64,
+        ];
+        let non_foldable = ReturnTypeArgs {
+            arg_types: &arg_types,
+            scalar_arguments: &[None, None],
+            nullables: &[false, true],
+        };
+        let err = resolve_depth(&non_foldable, Some(1)).unwrap_err();
+        assert!(err.to_string().contains("must be a literal"));
+    }
+
+    #[test]
+    fn get_base_type_descends_through_fixed_size_list_under_large_list() {
+        // A `LargeList` whose elements are `FixedSizeList`s must still descend
+        // through the `FixedSizeList` child: the `LargeList` arm's guard previously
+        // only recognized a `LargeList` child, so this case fell through to
+        // "already flat" and reported the unpeeled `LargeList<FixedSizeList<_>>`
+        // input type instead of the fully-flattened base type `flatten_internal`
+        // actually produces for `array_flatten_deep`.
+        let item_field = Arc::new(Field::new("item", DataType::Int32, true));
+        let fixed_size = DataType::FixedSizeList(Arc::clone(&item_field), 2);
+        let data_type = DataType::LargeList(Arc::new(Field::new("item", fixed_size, true)));
+
+        let base = get_base_type(&data_type, None).unwrap();
+        assert_eq!(base, DataType::LargeList(item_field));
+    }
+
+    #[test]
+    fn fixed_size_list_flattens_through_flatten_inner() {
+        // FixedSizeList(2) column [[1, 2], [3, 4], [5, 6]] flattened by one level
+        // becomes List<Int32> with the same rows.
+        let field = Arc::new(Field::new("item", DataType::Int32, true));
+        let values = Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5, 6])) as ArrayRef;
+        let fixed = FixedSizeListArray::new(field, 2, values, None);
+
+        let result = flatten_inner(&[Arc::new(fixed)], Some(1)).unwrap();
+        let list = as_list_array(&result).unwrap();
+        assert_eq!(list.len(), 3);
+        assert_eq!(int32_values(list.values()), &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(int32_values(list.value(0).as_ref()), &[1, 2]);
+        assert_eq!(int32_values(list.value(2).as_ref()), &[5, 6]);
+    }
+
+    #[test]
+    fn nested_fixed_size_list_of_list_flattens_fully() {
+        // FixedSizeList(2) of List<Int32>: [[[1, 2], [3]], [[4], [5, 6]]], fully
+        // flattened (array_flatten_deep) to [[1, 2, 3], [4, 5, 6]].
+        let inner = int32_list(vec![0, 2, 3, 4, 6], vec![1, 2, 3, 4, 5, 6]);
+        let child_field = Arc::new(Field::new("item", inner.data_type().to_owned(), true));
+        let fixed = FixedSizeListArray::new(child_field, 2, Arc::new(inner), None);
+
+        let result = flatten_inner(&[Arc::new(fixed)], None).unwrap();
+        let list = as_list_array(&result).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(int32_values(list.values()), &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(int32_values(list.value(0).as_ref()), &[1, 2, 3]);
+        assert_eq!(int32_values(list.value(1).as_ref()), &[4, 5, 6]);
+    }
+
+    #[test]
+    fn large_list_of_fixed_size_list_flattens_fully() {
+        // LargeList<FixedSizeList(2)<Int32>>: a single row holding 3 fixed-size
+        // elements [[1, 2], [3, 4], [5, 6]], fully flattened (array_flatten_deep)
+        // to [1, 2, 3, 4, 5, 6]. Unlike the rest of this test module, this one
+        // exercises the `i64`-offset (`LargeList`) path rather than `i32`.
+        let item_field = Arc::new(Field::new("item", DataType::Int32, true));
+        let values = Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5, 6])) as ArrayRef;
+        let fixed = FixedSizeListArray::new(item_field, 2, values, None);
+
+        let outer_field = Arc::new(Field::new("item", fixed.data_type().to_owned(), true));
+        let outer = GenericListArray::<i64>::new(
+            outer_field,
+            OffsetBuffer::new(vec![0i64, 3].into()),
+            Arc::new(fixed),
+            None,
+        );
+
+        let result = flatten_inner(&[Arc::new(outer)], None).unwrap();
+        let list = as_large_list_array(&result).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(int32_values(list.values()), &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(int32_values(list.value(0).as_ref()), &[1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn array_flatten_deep_recurses_to_base_type() {
+        // array_flatten_deep([[[1, 2], [3, 4]]]) == [1, 2, 3, 4], unlike the
+        // single-step `flatten` default which only peels one level.
+        let result = flatten_inner(&[Arc::new(three_level())], None).unwrap();
+        let list = as_list_array(&result).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(int32_values(list.values()), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn flatten_single_step_default_peels_only_one_level() {
+        // The default `flatten` call removes exactly one level of nesting, the
+        // Postgres-style semantics this request introduced, leaving the fully
+        // recursive behavior as the `array_flatten_deep` opt-in.
+        let shallow = flatten_inner(&[Arc::new(three_level())], Some(1)).unwrap();
+        let shallow_list = as_list_array(&shallow).unwrap();
+        assert_eq!(shallow_list.len(), 1);
+
+        let middle = as_generic_list_array::<i32>(shallow_list.values()).unwrap();
+        assert_eq!(middle.len(), 2);
+        assert_eq!(int32_values(middle.value(0).as_ref()), &[1, 2]);
+        assert_eq!(int32_values(middle.value(1).as_ref()), &[3, 4]);
+    }
+}